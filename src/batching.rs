@@ -0,0 +1,31 @@
+use std::ops::Range;
+
+/// Packs `texts` into batches (expressed as index ranges into `texts`) whose cumulative
+/// approximate token count stays at or under `max_tokens`, flushing a batch whenever the next
+/// text would push it over budget. A single text that already exceeds `max_tokens` on its own
+/// still gets its own one-item batch rather than being dropped or split.
+pub fn token_batches(texts: &[String], max_tokens: usize) -> Vec<Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0usize;
+    let mut current_tokens = 0usize;
+
+    for (i, text) in texts.iter().enumerate() {
+        let tokens = approx_token_count(text);
+        if i > start && current_tokens + tokens > max_tokens {
+            batches.push(start..i);
+            start = i;
+            current_tokens = 0;
+        }
+        current_tokens += tokens;
+    }
+    if start < texts.len() {
+        batches.push(start..texts.len());
+    }
+    batches
+}
+
+/// Approximates a text's token count by its whitespace-separated word count, counting an
+/// empty text as one token.
+fn approx_token_count(text: &str) -> usize {
+    text.split_whitespace().count().max(1)
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+/// Standard BM25 term-frequency saturation constant.
+const K1: f32 = 1.2;
+/// Standard BM25 length-normalization constant.
+const B: f32 = 0.75;
+
+/// In-memory inverted index over a fixed set of chunks, used to compute BM25 lexical scores
+/// alongside the semantic (cosine) scores so exact keyword matches aren't missed.
+pub struct Bm25Index {
+    /// token -> list of (chunk_id, term frequency in that chunk)
+    postings: HashMap<String, Vec<(usize, usize)>>,
+    /// token -> number of chunks containing it
+    doc_freq: HashMap<String, usize>,
+    chunk_lengths: Vec<usize>,
+    avg_chunk_length: f64,
+    num_chunks: usize,
+}
+
+impl Bm25Index {
+    /// Builds the index from chunk texts; `chunk_texts[i]` must correspond to chunk id `i`.
+    pub fn build(chunk_texts: &[String]) -> Self {
+        let mut postings: HashMap<String, Vec<(usize, usize)>> = HashMap::new();
+        let mut doc_freq: HashMap<String, usize> = HashMap::new();
+        let mut chunk_lengths = Vec::with_capacity(chunk_texts.len());
+        let mut total_length = 0usize;
+
+        for (chunk_id, text) in chunk_texts.iter().enumerate() {
+            let tokens = tokenize(text);
+            chunk_lengths.push(tokens.len());
+            total_length += tokens.len();
+
+            let mut term_freq: HashMap<String, usize> = HashMap::new();
+            for token in tokens {
+                *term_freq.entry(token).or_insert(0) += 1;
+            }
+            for (token, tf) in term_freq {
+                postings.entry(token.clone()).or_default().push((chunk_id, tf));
+                *doc_freq.entry(token).or_insert(0) += 1;
+            }
+        }
+
+        let num_chunks = chunk_texts.len();
+        let avg_chunk_length = if num_chunks > 0 {
+            total_length as f64 / num_chunks as f64
+        } else {
+            0.0
+        };
+
+        Bm25Index {
+            postings,
+            doc_freq,
+            chunk_lengths,
+            avg_chunk_length,
+            num_chunks,
+        }
+    }
+
+    /// Computes a BM25 score for every chunk against `query` (0.0 for chunks matching no
+    /// query term). The returned vector has one entry per chunk, in chunk-id order.
+    pub fn score(&self, query: &str) -> Vec<f32> {
+        let mut scores = vec![0.0f32; self.num_chunks];
+        if self.avg_chunk_length == 0.0 {
+            return scores;
+        }
+
+        for term in tokenize(query) {
+            let Some(df) = self.doc_freq.get(&term) else {
+                continue;
+            };
+            let Some(postings) = self.postings.get(&term) else {
+                continue;
+            };
+            let idf = idf(self.num_chunks, *df);
+
+            for &(chunk_id, tf) in postings {
+                let len_d = self.chunk_lengths[chunk_id] as f64;
+                let tf = tf as f64;
+                let denom = tf + K1 as f64 * (1.0 - B as f64 + B as f64 * (len_d / self.avg_chunk_length));
+                scores[chunk_id] += (idf * (tf * (K1 as f64 + 1.0)) / denom) as f32;
+            }
+        }
+        scores
+    }
+}
+
+fn idf(num_chunks: usize, doc_freq: usize) -> f64 {
+    let n = num_chunks as f64;
+    let df = doc_freq as f64;
+    ((n - df + 0.5) / (df + 0.5) + 1.0).ln()
+}
+
+/// Lowercases and splits on whitespace, trimming leading/trailing non-alphanumeric characters
+/// from each token so punctuation doesn't fragment matches.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Min-max normalizes `scores` into `[0, 1]`. A flat vector (all values equal, including all
+/// zero) normalizes to all zeros rather than dividing by zero.
+pub fn normalize_min_max(scores: &[f32]) -> Vec<f32> {
+    let min = scores.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = scores.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    let range = max - min;
+    if !range.is_finite() || range <= f32::EPSILON {
+        return vec![0.0; scores.len()];
+    }
+    scores.iter().map(|s| (s - min) / range).collect()
+}
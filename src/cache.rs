@@ -0,0 +1,266 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use sha1::{Digest, Sha1};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Bumped whenever the on-disk layout or embedding semantics change; a mismatch
+/// triggers a full cache rebuild instead of serving stale/incompatible embeddings.
+const SCHEMA_VERSION: i64 = 1;
+
+/// A single cached chunk, as loaded straight from the DB without touching the model.
+pub struct CachedChunk {
+    pub text: String,
+    pub range: Range<usize>,
+    pub embedding: Vec<f32>,
+}
+
+/// Thin wrapper around the SQLite-backed embedding cache.
+///
+/// Schema:
+/// - `meta(key, value)`           — holds the `schema_version` and `embedding_key` rows.
+/// - `files(id, path, mtime, digest)` — one row per indexed file.
+/// - `chunks(file_id, chunk_index, text, range_start, range_end, embedding)` — one row per chunk.
+pub struct Cache {
+    conn: Connection,
+}
+
+impl Cache {
+    /// Opens (creating if necessary) the cache database at `cache_dir/embeddings.sqlite3`.
+    /// If the stored schema version or `embedding_key` doesn't match, the cache is dropped and
+    /// recreated — `embedding_key` should identify the model, provider, and chunking params in
+    /// use, so switching any of them invalidates previously cached embeddings instead of mixing
+    /// incompatible embedding spaces together.
+    pub fn open(cache_dir: &Path, embedding_key: &str) -> Result<Self> {
+        fs_create_dir_all(cache_dir)?;
+        let db_path = cache_dir.join("embeddings.sqlite3");
+        let conn = Connection::open(&db_path)
+            .with_context(|| format!("failed to open cache database at {}", db_path.display()))?;
+        // SQLite leaves foreign keys off by default; the ON DELETE CASCADE on `chunks` needs them.
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        let cache = Cache { conn };
+        cache.init_schema(embedding_key)?;
+        Ok(cache)
+    }
+
+    fn init_schema(&self, embedding_key: &str) -> Result<()> {
+        let stored_version: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = 'schema_version'",
+                [],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()
+            .unwrap_or(None)
+            .and_then(|v| v.parse().ok());
+
+        let stored_embedding_key: Option<String> = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = 'embedding_key'", [], |row| row.get(0))
+            .optional()
+            .unwrap_or(None);
+
+        let schema_stale = stored_version.is_some() && stored_version != Some(SCHEMA_VERSION);
+        let embedding_key_stale = stored_embedding_key.is_some() && stored_embedding_key.as_deref() != Some(embedding_key);
+
+        if schema_stale || embedding_key_stale {
+            self.conn.execute_batch(
+                "DROP TABLE IF EXISTS chunks; DROP TABLE IF EXISTS files; DROP TABLE IF EXISTS meta;",
+            )?;
+        }
+
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS files (
+                 id INTEGER PRIMARY KEY,
+                 path TEXT NOT NULL UNIQUE,
+                 mtime INTEGER NOT NULL,
+                 digest TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS chunks (
+                 id INTEGER PRIMARY KEY,
+                 file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+                 chunk_index INTEGER NOT NULL,
+                 text TEXT NOT NULL,
+                 range_start INTEGER NOT NULL,
+                 range_end INTEGER NOT NULL,
+                 embedding BLOB NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_chunks_file_id ON chunks(file_id);",
+        )?;
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES ('schema_version', ?1)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![SCHEMA_VERSION.to_string()],
+        )?;
+        self.conn.execute(
+            "INSERT INTO meta (key, value) VALUES ('embedding_key', ?1)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![embedding_key],
+        )?;
+        Ok(())
+    }
+
+    /// Looks up a file by path and, if its digest still matches, returns its cached chunks.
+    /// Returns `None` on a miss (new file, changed content, or no cache entry).
+    pub fn lookup(&self, path: &Path, digest: &str) -> Result<Option<Vec<CachedChunk>>> {
+        let path_str = path.to_string_lossy();
+        let file_id: Option<(i64, String)> = self
+            .conn
+            .query_row(
+                "SELECT id, digest FROM files WHERE path = ?1",
+                params![path_str],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        let Some((file_id, stored_digest)) = file_id else {
+            return Ok(None);
+        };
+        if stored_digest != digest {
+            return Ok(None);
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT text, range_start, range_end, embedding FROM chunks WHERE file_id = ?1 ORDER BY chunk_index ASC",
+        )?;
+        let rows = stmt
+            .query_map(params![file_id], |row| {
+                let text: String = row.get(0)?;
+                let range_start: i64 = row.get(1)?;
+                let range_end: i64 = row.get(2)?;
+                let blob: Vec<u8> = row.get(3)?;
+                Ok(CachedChunk {
+                    text,
+                    range: range_start as usize..range_end as usize,
+                    embedding: bytes_to_f32_vec(&blob),
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(Some(rows))
+    }
+
+    /// Replaces the cached entry for `path` with `mtime`/`digest` and its chunk embeddings.
+    /// Runs as a single transaction so a crash mid-write can't leave a half-updated file.
+    pub fn store(
+        &mut self,
+        path: &Path,
+        mtime: i64,
+        digest: &str,
+        chunks: &[(String, Range<usize>, Vec<f32>)],
+    ) -> Result<()> {
+        let path_str = path.to_string_lossy().to_string();
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM files WHERE path = ?1",
+            params![path_str],
+        )?;
+        tx.execute(
+            "INSERT INTO files (path, mtime, digest) VALUES (?1, ?2, ?3)",
+            params![path_str, mtime, digest],
+        )?;
+        let file_id = tx.last_insert_rowid();
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO chunks (file_id, chunk_index, text, range_start, range_end, embedding) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?;
+            for (index, (text, range, embedding)) in chunks.iter().enumerate() {
+                stmt.execute(params![
+                    file_id,
+                    index as i64,
+                    text,
+                    range.start as i64,
+                    range.end as i64,
+                    f32_slice_to_bytes(embedding)
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Removes every cached entry for files that no longer exist, e.g. deleted since the
+    /// last run, keeping the cache from growing unboundedly stale.
+    pub fn evict_missing(&mut self, present_paths: &[PathBuf]) -> Result<()> {
+        let present: std::collections::HashSet<String> = present_paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        let mut stale = Vec::new();
+        {
+            let mut stmt = self.conn.prepare("SELECT path FROM files")?;
+            let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+            for row in rows {
+                let path = row?;
+                if !present.contains(&path) {
+                    stale.push(path);
+                }
+            }
+        }
+        let tx = self.conn.transaction()?;
+        for path in stale {
+            tx.execute("DELETE FROM files WHERE path = ?1", params![path])?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+/// Computes the SHA-1 hex digest of a file's contents, used as the cache invalidation key
+/// (the file's mtime is stored next to it but not compared).
+pub fn digest_bytes(content: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(content);
+    let result = hasher.finalize();
+    result.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Returns a file's modification time as a Unix timestamp in seconds, or 0 if unavailable.
+pub fn mtime_secs(metadata: &std::fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Builds the cache's `embedding_key`: a string identifying everything that determines the
+/// embedding space and chunk boundaries of what gets cached. Cached chunks are only reused
+/// when this matches exactly, so switching model, provider, or chunking params rebuilds the
+/// cache instead of mixing incompatible embeddings together.
+pub fn embedding_key(model: &str, provider: &str, max_chunk_tokens: usize, chunk_overlap: usize) -> String {
+    format!("{model}|{provider}|{max_chunk_tokens}|{chunk_overlap}")
+}
+
+/// Resolves the cache directory to use: the user-provided `--cache-dir`, or `sff` under the
+/// platform cache dir (on Linux `$XDG_CACHE_HOME`, falling back to `~/.cache`) otherwise.
+pub fn resolve_cache_dir(cache_dir: &Option<PathBuf>) -> Result<PathBuf> {
+    if let Some(dir) = cache_dir {
+        return Ok(dir.clone());
+    }
+    let base = dirs::cache_dir().context("could not determine a default cache directory")?;
+    Ok(base.join("sff"))
+}
+
+fn f32_slice_to_bytes(values: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(values.len() * 4);
+    for v in values {
+        bytes.extend_from_slice(&v.to_le_bytes());
+    }
+    bytes
+}
+
+fn bytes_to_f32_vec(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+fn fs_create_dir_all(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("failed to create cache directory {}", dir.display()))
+}
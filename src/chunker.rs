@@ -0,0 +1,254 @@
+use std::ops::Range;
+
+/// Parameters controlling how file content is split into embeddable chunks.
+pub struct ChunkParams {
+    pub max_chunk_tokens: usize,
+    pub chunk_overlap: usize,
+}
+
+/// A chunk produced by the splitter: its text and the byte range, in the original file
+/// content, of the units packed into it. Overlap words carried over from the previous chunk
+/// lie before `range.start`.
+pub struct Chunk {
+    pub text: String,
+    pub range: Range<usize>,
+}
+
+/// A packable unit of text: either a fenced code block (kept intact) or a sentence/paragraph
+/// fragment of prose. Units are the grain that greedy packing operates on.
+struct Unit {
+    range: Range<usize>,
+    word_count: usize,
+    is_code: bool,
+}
+
+/// Splits `content` into chunks respecting Markdown structure (for `.md`/`.mdx` files) and a
+/// token budget, carrying `chunk_overlap` trailing words from each chunk into the next.
+pub fn chunk_content(content: &str, is_markdown: bool, params: &ChunkParams) -> Vec<Chunk> {
+    let blocks = split_blocks(content, is_markdown);
+    let units: Vec<Unit> = blocks
+        .into_iter()
+        .flat_map(|block| match block {
+            Block::Code(range) => vec![Unit {
+                word_count: word_count(&content[range.clone()]),
+                range,
+                is_code: true,
+            }],
+            Block::Text(range) => split_into_sentence_units(content, range),
+        })
+        .collect();
+
+    pack_units(content, &units, params)
+}
+
+enum Block {
+    Code(Range<usize>),
+    Text(Range<usize>),
+}
+
+/// Splits content into text and code blocks: each fenced code block (``` or ~~~) becomes a
+/// code block, and each heading line (`#`..`######`) outside a fence starts a new text block.
+/// Non-Markdown files are returned as a single text block.
+fn split_blocks(content: &str, is_markdown: bool) -> Vec<Block> {
+    if !is_markdown {
+        return if content.is_empty() {
+            Vec::new()
+        } else {
+            vec![Block::Text(0..content.len())]
+        };
+    }
+
+    let mut blocks = Vec::new();
+    let mut block_start = 0usize;
+    let mut in_code = false;
+    let mut code_start = 0usize;
+
+    for (line_start, line) in lines_with_offsets(content) {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if in_code {
+                let end = line_start + line.len();
+                blocks.push(Block::Code(code_start..end));
+                block_start = end;
+                in_code = false;
+            } else {
+                if block_start < line_start {
+                    blocks.push(Block::Text(block_start..line_start));
+                }
+                code_start = line_start;
+                in_code = true;
+            }
+        } else if !in_code && is_heading_line(trimmed) && block_start < line_start {
+            blocks.push(Block::Text(block_start..line_start));
+            block_start = line_start;
+        }
+    }
+
+    if in_code {
+        blocks.push(Block::Code(code_start..content.len()));
+    } else if block_start < content.len() {
+        blocks.push(Block::Text(block_start..content.len()));
+    }
+    blocks
+}
+
+fn is_heading_line(trimmed: &str) -> bool {
+    let hashes = trimmed.chars().take_while(|c| *c == '#').count();
+    (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(|c: char| c == ' ' || c.is_whitespace())
+}
+
+/// Yields `(byte_offset, line)` pairs, including the trailing newline in each line so that
+/// offsets stay aligned with the original content.
+fn lines_with_offsets(content: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    content.split_inclusive('\n').map(move |line| {
+        let start = offset;
+        offset += line.len();
+        (start, line)
+    })
+}
+
+/// Splits a text block into paragraphs (on blank lines) and each paragraph into sentences
+/// (on `.`/`!`/`?` followed by whitespace), producing one [`Unit`] per sentence.
+fn split_into_sentence_units(content: &str, range: Range<usize>) -> Vec<Unit> {
+    let mut units = Vec::new();
+    for paragraph_range in split_paragraphs(content, range) {
+        let text = &content[paragraph_range.clone()];
+        if text.trim().is_empty() {
+            continue;
+        }
+        for sentence_range in split_sentences(text, paragraph_range.start) {
+            let sentence = &content[sentence_range.clone()];
+            if sentence.trim().is_empty() {
+                continue;
+            }
+            units.push(Unit {
+                word_count: word_count(sentence),
+                range: sentence_range,
+                is_code: false,
+            });
+        }
+    }
+    units
+}
+
+fn split_paragraphs(content: &str, range: Range<usize>) -> Vec<Range<usize>> {
+    let text = &content[range.clone()];
+    let mut paragraphs = Vec::new();
+    let mut start = 0usize;
+    let bytes = text.as_bytes();
+    let mut i = 0usize;
+    while i < bytes.len() {
+        if bytes[i] == b'\n' && i + 1 < bytes.len() && bytes[i + 1] == b'\n' {
+            if i > start {
+                paragraphs.push(range.start + start..range.start + i);
+            }
+            i += 2;
+            while i < bytes.len() && bytes[i] == b'\n' {
+                i += 1;
+            }
+            start = i;
+        } else {
+            i += 1;
+        }
+    }
+    if start < text.len() {
+        paragraphs.push(range.start + start..range.start + text.len());
+    }
+    paragraphs
+}
+
+fn split_sentences(text: &str, base_offset: usize) -> Vec<Range<usize>> {
+    let mut sentences = Vec::new();
+    let mut start = 0usize;
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    for idx in 0..chars.len() {
+        let (byte_idx, ch) = chars[idx];
+        if matches!(ch, '.' | '!' | '?') {
+            let next_is_boundary = chars
+                .get(idx + 1)
+                .map(|(_, c)| c.is_whitespace())
+                .unwrap_or(true);
+            if next_is_boundary {
+                let end = byte_idx + ch.len_utf8();
+                sentences.push(base_offset + start..base_offset + end);
+                start = end;
+            }
+        }
+    }
+    if start < text.len() {
+        sentences.push(base_offset + start..base_offset + text.len());
+    }
+    sentences
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
+
+/// Greedily packs units into chunks of at most `max_chunk_tokens` words, carrying the last
+/// `chunk_overlap` words of each chunk into the start of the next so context isn't severed at
+/// a chunk boundary. The carried-over words are not counted against `max_chunk_tokens`, so a
+/// chunk's text can run up to `chunk_overlap` words over it. A single unit already over budget
+/// (e.g. a long code block) is emitted as its own chunk rather than being split.
+fn pack_units(content: &str, units: &[Unit], params: &ChunkParams) -> Vec<Chunk> {
+    let mut chunks = Vec::new();
+    let mut current: Vec<&Unit> = Vec::new();
+    let mut current_words = 0usize;
+    let mut pending_overlap: Option<String> = None;
+
+    for unit in units {
+        if !current.is_empty() && current_words + unit.word_count > params.max_chunk_tokens {
+            pending_overlap = flush(content, &mut current, &mut current_words, &mut chunks, pending_overlap.take(), params.chunk_overlap);
+        }
+        current.push(unit);
+        current_words += unit.word_count;
+    }
+    flush(content, &mut current, &mut current_words, &mut chunks, pending_overlap, params.chunk_overlap);
+
+    chunks
+}
+
+/// Finalizes the chunk accumulated in `current` (prepending any carried-over overlap text from
+/// the previous chunk), pushes it to `chunks`, and returns the overlap text to carry into the
+/// *next* chunk.
+fn flush(
+    content: &str,
+    current: &mut Vec<&Unit>,
+    current_words: &mut usize,
+    chunks: &mut Vec<Chunk>,
+    overlap_prefix: Option<String>,
+    chunk_overlap: usize,
+) -> Option<String> {
+    if current.is_empty() {
+        return overlap_prefix;
+    }
+    let start = current.first().unwrap().range.start;
+    let end = current.last().unwrap().range.end;
+    let joiner = if current.iter().any(|u| u.is_code) { "\n\n" } else { " " };
+    let mut text = current
+        .iter()
+        .map(|u| content[u.range.clone()].trim())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(joiner);
+    if let Some(prefix) = overlap_prefix {
+        text = format!("{prefix} {text}");
+    }
+
+    let next_overlap = if chunk_overlap > 0 {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.len() > chunk_overlap {
+            Some(words[words.len() - chunk_overlap..].join(" "))
+        } else {
+            Some(text.clone())
+        }
+    } else {
+        None
+    };
+
+    chunks.push(Chunk { text, range: start..end });
+    current.clear();
+    *current_words = 0;
+    next_overlap
+}
@@ -1,7 +1,7 @@
 use clap::Parser;
 use std::path::PathBuf;
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(
     name = "sff",
     author = "Dominik Weckmüller",
@@ -14,8 +14,7 @@ pub struct Args {
     #[arg(short = 'p', long, default_value = ".")]
     pub path: PathBuf,
 
-    /// The semantic search query
-    #[arg(required = true)]
+    /// The semantic search query (not required with --watch, which reads queries from stdin)
     pub query: Vec<String>,
 
     /// Model to use for embeddings, from Hugging Face Hub or local path
@@ -41,4 +40,49 @@ pub struct Args {
     /// Choose file extensions
     #[arg(short = 'e', long, default_values = ["txt", "md", "mdx", "org"])]
     pub extension: Vec<String>,
+
+    /// Directory to store the persistent embedding cache in (default: XDG cache dir)
+    #[arg(long)]
+    pub cache_dir: Option<PathBuf>,
+
+    /// Disable the persistent embedding cache and re-embed every file
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Maximum approximate tokens (whitespace words) per chunk, not counting the words carried over by --chunk-overlap
+    #[arg(long, default_value_t = 200)]
+    pub max_chunk_tokens: usize,
+
+    /// Number of trailing words from the previous chunk carried into the next
+    #[arg(long, default_value_t = 20)]
+    pub chunk_overlap: usize,
+
+    /// Embedding provider to use
+    #[arg(long, default_value = "model2vec")]
+    pub provider: String,
+
+    /// HTTP endpoint for the `ollama`/`openai` providers (defaults to each provider's usual URL)
+    #[arg(long)]
+    pub endpoint: Option<String>,
+
+    /// Maximum number of texts to send in a single request to a remote provider
+    #[arg(long, default_value_t = 64)]
+    pub max_texts_per_request: usize,
+
+    /// Weight given to the semantic score when fusing with the lexical (BM25) score in hybrid mode
+    #[arg(long, default_value_t = 0.5)]
+    pub alpha: f32,
+
+    /// Scoring mode: semantic (embeddings only), lexical (BM25 only), or hybrid (fused).
+    /// Defaults to semantic to preserve prior output; pass `--mode hybrid` to opt into BM25 fusion.
+    #[arg(long, default_value = "semantic")]
+    pub mode: String,
+
+    /// Maximum approximate tokens (whitespace words) packed into a single embedding request
+    #[arg(long, default_value_t = 2000)]
+    pub batch_max_tokens: usize,
+
+    /// Watch the search path for changes, incrementally re-indexing, and serve queries from stdin
+    #[arg(short = 'w', long)]
+    pub watch: bool,
 }
@@ -0,0 +1,198 @@
+use anyhow::{anyhow, bail, Context, Result};
+use model2vec_rs::model::StaticModel;
+use serde::Deserialize;
+use std::thread;
+use std::time::Duration;
+
+/// Source of text embeddings. Implemented once for the bundled local `model2vec` model and
+/// once for remote HTTP providers (Ollama / OpenAI-compatible), so the rest of the pipeline
+/// doesn't care which one produced a vector.
+pub trait Embedder: Send + Sync {
+    /// Embeds a batch of texts, returning one vector per input in the same order.
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>>;
+}
+
+/// Local, in-process embedder backed by `model2vec_rs::StaticModel`.
+pub struct Model2VecEmbedder {
+    model: StaticModel,
+}
+
+impl Model2VecEmbedder {
+    pub fn load(model_name: &str) -> Result<Self> {
+        let model = StaticModel::from_pretrained(model_name, None, Some(true), None)?; // normalize=true
+        Ok(Model2VecEmbedder { model })
+    }
+}
+
+impl Embedder for Model2VecEmbedder {
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        Ok(self.model.encode(texts))
+    }
+}
+
+/// Which wire format to speak to the remote embedding endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteProvider {
+    Ollama,
+    OpenAi,
+}
+
+/// Embedder that POSTs batches of text to a remote Ollama or OpenAI-compatible endpoint.
+pub struct RemoteEmbedder {
+    client: reqwest::blocking::Client,
+    provider: RemoteProvider,
+    endpoint: String,
+    model: String,
+    api_key: Option<String>,
+    max_texts_per_request: usize,
+}
+
+impl RemoteEmbedder {
+    pub fn new(
+        provider: RemoteProvider,
+        endpoint: String,
+        model: String,
+        api_key: Option<String>,
+        max_texts_per_request: usize,
+    ) -> Self {
+        RemoteEmbedder {
+            client: reqwest::blocking::Client::new(),
+            provider,
+            endpoint,
+            model,
+            api_key,
+            max_texts_per_request: max_texts_per_request.max(1),
+        }
+    }
+
+    fn post_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        const MAX_RETRIES: u32 = 5;
+        let mut attempt = 0u32;
+        loop {
+            let mut request = match self.provider {
+                RemoteProvider::Ollama => self
+                    .client
+                    .post(&self.endpoint)
+                    .json(&OllamaEmbedRequest {
+                        model: &self.model,
+                        input: texts,
+                    }),
+                RemoteProvider::OpenAi => self.client.post(&self.endpoint).json(&OpenAiEmbedRequest {
+                    model: &self.model,
+                    input: texts,
+                }),
+            };
+            if let Some(key) = &self.api_key {
+                request = request.bearer_auth(key);
+            }
+
+            let response = request.send().context("failed to reach embedding endpoint")?;
+
+            if response.status().as_u16() == 429 && attempt < MAX_RETRIES {
+                let delay = Duration::from_millis(200 * 2u64.pow(attempt));
+                thread::sleep(delay);
+                attempt += 1;
+                continue;
+            }
+
+            if !response.status().is_success() {
+                bail!(
+                    "embedding endpoint returned {}: {}",
+                    response.status(),
+                    response.text().unwrap_or_default()
+                );
+            }
+
+            let embeddings = match self.provider {
+                RemoteProvider::Ollama => {
+                    let body: OllamaEmbedResponse = response.json().context("invalid Ollama embedding response")?;
+                    body.embeddings
+                }
+                RemoteProvider::OpenAi => {
+                    let body: OpenAiEmbedResponse = response.json().context("invalid OpenAI-compatible embedding response")?;
+                    let mut data = body.data;
+                    // The spec only guarantees `index` maps back to input order, not that `data`
+                    // is returned in that order; sort before dropping it so a reordering server
+                    // doesn't silently misalign vectors with their input texts.
+                    data.sort_by_key(|d| d.index);
+                    data.into_iter().map(|d| d.embedding).collect()
+                }
+            };
+
+            if embeddings.len() != texts.len() {
+                bail!(
+                    "embedding endpoint returned {} embedding(s) for {} input(s)",
+                    embeddings.len(),
+                    texts.len()
+                );
+            }
+
+            return Ok(embeddings);
+        }
+    }
+}
+
+impl Embedder for RemoteEmbedder {
+    fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for batch in texts.chunks(self.max_texts_per_request) {
+            embeddings.extend(self.post_batch(batch)?);
+        }
+        Ok(embeddings)
+    }
+}
+
+#[derive(serde::Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+#[derive(serde::Serialize)]
+struct OpenAiEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedResponse {
+    data: Vec<OpenAiEmbedDatum>,
+}
+
+#[derive(Deserialize)]
+struct OpenAiEmbedDatum {
+    embedding: Vec<f32>,
+    index: usize,
+}
+
+/// Parses the `--provider` flag value into a concrete [`RemoteProvider`], or `None` for the
+/// local `model2vec` provider.
+pub fn parse_provider(provider: &str) -> Result<Option<RemoteProvider>> {
+    match provider {
+        "model2vec" => Ok(None),
+        "ollama" => Ok(Some(RemoteProvider::Ollama)),
+        "openai" => Ok(Some(RemoteProvider::OpenAi)),
+        other => Err(anyhow!("unknown embedding provider '{other}' (expected model2vec, ollama, or openai)")),
+    }
+}
+
+/// Default endpoint for a remote provider when `--endpoint` isn't given.
+pub fn default_endpoint(provider: RemoteProvider) -> &'static str {
+    match provider {
+        RemoteProvider::Ollama => "http://localhost:11434/api/embed",
+        RemoteProvider::OpenAi => "https://api.openai.com/v1/embeddings",
+    }
+}
+
+/// Env var consulted for the API key of a remote provider (unset is fine for local Ollama).
+pub fn api_key_env_var(provider: RemoteProvider) -> &'static str {
+    match provider {
+        RemoteProvider::Ollama => "OLLAMA_API_KEY",
+        RemoteProvider::OpenAi => "OPENAI_API_KEY",
+    }
+}
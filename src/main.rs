@@ -1,35 +1,54 @@
+mod batching;
+mod bm25;
+mod cache;
+mod chunker;
 mod cli;
+mod embedder;
+mod watch;
 
+use crate::cache::Cache;
+use crate::chunker::ChunkParams;
 use crate::cli::Args;
+use crate::embedder::{Embedder, Model2VecEmbedder, RemoteEmbedder};
 
 use anyhow::{Context, Result};
 use clap::Parser;
 use comfy_table::{presets::UTF8_FULL, Cell, ContentArrangement, Table};
 use indicatif::{ProgressBar, ProgressStyle};
-use model2vec_rs::model::StaticModel; // Using the provided model2vec-rs
 use ndarray::{Array1, ArrayView1};
 use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
 use rayon::prelude::*;
 use std::collections::HashSet;
 use std::fs;
+use std::io::BufRead;
+use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use std::time::{Instant};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
-const CHUNK_EMBEDDING_BATCH_SIZE: usize = 128; // How many text chunks to embed in one go per parallel task
-const WORD_CHUNK_SIZE: usize = 20; // How many words per text chunk
-
 #[derive(Debug, Clone)]
 struct TextChunk {
     path: PathBuf,
     text: String,
+    range: Range<usize>,
 }
 
 struct SearchResult {
     score: f32,
     path: PathBuf,
     chunk: String,
+    range: Range<usize>,
+}
+
+/// The in-memory index: every chunk across all indexed files plus its embedding, aligned by
+/// position, the number of distinct files they came from, and the BM25 inverted index built
+/// over the same chunks (built once here rather than per query).
+struct Index {
+    chunks: Vec<TextChunk>,
+    chunk_embeddings: Vec<Vec<f32>>,
+    file_count: usize,
+    bm25_index: bm25::Bm25Index,
 }
 
 const PATH_ENCODE_SET: &AsciiSet = &CONTROLS
@@ -70,53 +89,26 @@ fn main() -> Result<()> {
     let args = Args::parse();
     let query_string = args.query.join(" ");
 
-    // 1. DISCOVER, READ, AND CHUNK FILES
-    let (chunks, file_count) =
-        timed_block("File Discovery, Reading & Chunking", args.verbose, false, || {
-            let walker = WalkDir::new(&args.path).max_depth(if args.recursive { usize::MAX } else { 1 });
-            let collected_chunks: Vec<TextChunk> = walker
-                .into_iter()
-                .filter_map(Result::ok)
-                .par_bridge()
-                .filter(|e| e.file_type().is_file())
-                .filter_map(|entry| {
-                    let path = entry.path();
-                    let extension = path.extension().and_then(|s| s.to_str());
-                    match extension {
-                        Some("txt") | Some("md") | Some("mdx") => {
-                            match fs::read_to_string(path) {
-                                Ok(content) => Some((content, path.to_path_buf())),
-                                Err(e) => {
-                                    if args.verbose {
-                                        eprintln!("[VERBOSE] Failed to read {}: {}", path.display(), e);
-                                    }
-                                    None
-                                }
-                            }
-                        },
-                        _ => None,
-                    }
-                })
-                .flat_map(|(content, path)| {
-                    let words: Vec<&str> = content.split_whitespace().collect();
-                    words
-                        .chunks(WORD_CHUNK_SIZE)
-                        .map(|word_slice| TextChunk {
-                            path: path.clone(),
-                            text: word_slice.join(" "),
-                        })
-                        .collect::<Vec<_>>()
-                })
-                .collect();
-
-            let num_unique_files = {
-                let unique_paths: HashSet<_> = collected_chunks.iter().map(|c| &c.path).collect();
-                unique_paths.len()
-            };
-            (collected_chunks, num_unique_files)
-        });
+    if !matches!(args.mode.as_str(), "semantic" | "lexical" | "hybrid") {
+        anyhow::bail!("unknown mode '{}' (expected semantic, lexical, or hybrid)", args.mode);
+    }
+    if !args.watch && args.query.is_empty() {
+        anyhow::bail!("a search query is required unless --watch is set");
+    }
 
-    if chunks.is_empty() {
+    let mut cache = if args.no_cache {
+        None
+    } else {
+        let cache_dir = cache::resolve_cache_dir(&args.cache_dir)?;
+        let embedding_key = cache::embedding_key(&args.model, &args.provider, args.max_chunk_tokens, args.chunk_overlap);
+        Some(Cache::open(&cache_dir, &embedding_key)?)
+    };
+
+    let embedder: Arc<dyn Embedder> = load_embedder(&args, program_total_start_time)?;
+
+    let index = build_index(&args, cache.as_mut(), embedder.as_ref())?;
+
+    if index.chunks.is_empty() && !args.watch {
         println!(
             "No text files (.txt, .md, .mdx) found to search in '{}'.",
             args.path.display()
@@ -124,29 +116,151 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    // 2. LOAD MODEL
-    let model = timed_block("Model Loading", args.verbose, false, || {
-        StaticModel::from_pretrained(&args.model, None, Some(true), None) // normalize=true
+    if args.watch {
+        if !query_string.is_empty() {
+            let mut results = search(&index, embedder.as_ref(), &query_string, &args)?;
+            print_results(&mut results, &args, index.file_count, &query_string, program_total_start_time.elapsed());
+        }
+        return run_watch_mode(args, cache, embedder, index);
+    }
+
+    let mut results = search(&index, embedder.as_ref(), &query_string, &args)?;
+    print_results(&mut results, &args, index.file_count, &query_string, program_total_start_time.elapsed());
+
+    Ok(())
+}
+
+/// Loads the configured embedding provider (local `model2vec` or a remote HTTP backend).
+fn load_embedder(args: &Args, program_total_start_time: Instant) -> Result<Arc<dyn Embedder>> {
+    let embedder: Arc<dyn Embedder> = timed_block("Model Loading", args.verbose, false, || -> Result<Arc<dyn Embedder>> {
+        match embedder::parse_provider(&args.provider)? {
+            None => Ok(Arc::new(Model2VecEmbedder::load(&args.model)?)),
+            Some(provider) => {
+                let endpoint = args
+                    .endpoint
+                    .clone()
+                    .unwrap_or_else(|| embedder::default_endpoint(provider).to_string());
+                let api_key = std::env::var(embedder::api_key_env_var(provider)).ok();
+                Ok(Arc::new(RemoteEmbedder::new(
+                    provider,
+                    endpoint,
+                    args.model.clone(),
+                    api_key,
+                    args.max_texts_per_request,
+                )))
+            }
+        }
     })?;
-    
+
     if args.verbose && program_total_start_time.elapsed().as_secs_f32() > 0.5 {
-         eprintln!("[VERBOSE] Note: If model loading is slow (>500ms), it might be due to first-time download by hf-hub, or inefficiencies in the specific `model2vec-rs/model.rs::from_pretrained` version being used (e.g., for `unk_token` lookup). This part cannot be optimized further within `sff` itself without changing `model2vec-rs`."); // Changed fast_finder to sff
+        eprintln!("[VERBOSE] Note: If model loading is slow (>500ms), it might be due to first-time download by hf-hub, or inefficiencies in the specific `model2vec-rs/model.rs::from_pretrained` version being used (e.g., for `unk_token` lookup). This part cannot be optimized further within `sff` itself without changing `model2vec-rs`."); // Changed fast_finder to sff
     }
 
-    let model_arc = Arc::new(model);
+    Ok(embedder)
+}
 
-    // 3. ENCODE THE SEARCH QUERY
-    let query_embedding = timed_block("Query Embedding", args.verbose, true, || {
-        let query_embeddings_vec = model_arc.encode(&[query_string.clone()]);
-        query_embeddings_vec
+/// Walks `args.path`, loads unchanged files straight from the cache, chunks and embeds the
+/// rest, writes fresh entries back to the cache, and evicts entries for files that vanished.
+fn build_index(args: &Args, mut cache: Option<&mut Cache>, embedder: &dyn Embedder) -> Result<Index> {
+    // 1. DISCOVER AND READ FILES
+    let files = timed_block("File Discovery & Reading", args.verbose, false, || {
+        let walker = WalkDir::new(&args.path).max_depth(if args.recursive { usize::MAX } else { 1 });
+        let collected_files: Vec<(PathBuf, String, std::fs::Metadata)> = walker
             .into_iter()
-            .next()
-            .context("Failed to encode query string")
+            .filter_map(Result::ok)
+            .par_bridge()
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|entry| {
+                let path = entry.path();
+                let extension = path.extension().and_then(|s| s.to_str());
+                match extension {
+                    Some("txt") | Some("md") | Some("mdx") => {
+                        match fs::read_to_string(path).and_then(|content| {
+                            fs::metadata(path).map(|metadata| (content, metadata))
+                        }) {
+                            Ok((content, metadata)) => Some((path.to_path_buf(), content, metadata)),
+                            Err(e) => {
+                                if args.verbose {
+                                    eprintln!("[VERBOSE] Failed to read {}: {}", path.display(), e);
+                                }
+                                None
+                            }
+                        }
+                    },
+                    _ => None,
+                }
+            })
+            .collect();
+        collected_files
+    });
+
+    // 2. SPLIT INTO CACHE HITS (load embeddings straight from disk) AND MISSES (need chunking + embedding)
+    let (mut chunks, mut chunk_embeddings): (Vec<TextChunk>, Vec<Vec<f32>>) = (Vec::new(), Vec::new());
+    let mut pending_files: Vec<(PathBuf, i64, String, Range<usize>)> = Vec::new();
+
+    let chunk_params = ChunkParams {
+        max_chunk_tokens: args.max_chunk_tokens,
+        chunk_overlap: args.chunk_overlap,
+    };
+
+    timed_block("Cache Lookup & Chunking", args.verbose, false, || -> Result<()> {
+        for (path, content, metadata) in &files {
+            let digest = cache::digest_bytes(content.as_bytes());
+            let mtime = cache::mtime_secs(metadata);
+
+            let cached = match cache.as_deref() {
+                Some(cache) => cache.lookup(path, &digest)?,
+                None => None,
+            };
+
+            if let Some(cached_chunks) = cached {
+                for cached_chunk in cached_chunks {
+                    chunks.push(TextChunk {
+                        path: path.clone(),
+                        text: cached_chunk.text,
+                        range: cached_chunk.range,
+                    });
+                    chunk_embeddings.push(cached_chunk.embedding);
+                }
+                continue;
+            }
+
+            let is_markdown = matches!(
+                path.extension().and_then(|e| e.to_str()),
+                Some("md") | Some("mdx")
+            );
+            let start = chunks.len();
+            for chunk in chunker::chunk_content(content, is_markdown, &chunk_params) {
+                chunks.push(TextChunk {
+                    path: path.clone(),
+                    text: chunk.text,
+                    range: chunk.range,
+                });
+                chunk_embeddings.push(Vec::new()); // filled in once the model has run over this chunk
+            }
+            pending_files.push((path.clone(), mtime, digest, start..chunks.len()));
+        }
+        Ok(())
     })?;
 
-    // 4. GENERATE EMBEDDINGS FOR TEXT CHUNKS
-    let bar_chunk_embedding = ProgressBar::new(chunks.len() as u64);
-    if args.verbose || chunks.len() > 10000 {
+    let file_count = {
+        let unique_paths: HashSet<_> = files.iter().map(|(path, _, _)| path).collect();
+        unique_paths.len()
+    };
+
+    if chunks.is_empty() {
+        return Ok(Index {
+            chunks,
+            chunk_embeddings,
+            file_count,
+            bm25_index: bm25::Bm25Index::build(&[]),
+        });
+    }
+
+    // 3. GENERATE EMBEDDINGS FOR CHUNKS THAT MISSED THE CACHE
+    let pending_chunk_count: usize = pending_files.iter().map(|(_, _, _, range)| range.len()).sum();
+    let bar_chunk_embedding = ProgressBar::new(pending_chunk_count as u64);
+    if args.verbose || pending_chunk_count > 10000 {
         bar_chunk_embedding.set_style(
             ProgressStyle::default_bar()
                 .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta}) {msg}")?
@@ -157,85 +271,220 @@ fn main() -> Result<()> {
     }
     bar_chunk_embedding.set_message("Embedding file chunks...");
 
-    let chunk_embeddings: Vec<Vec<f32>> = timed_block("Chunk Embedding Generation", args.verbose, true, || {
-        chunks
-            .par_chunks(CHUNK_EMBEDDING_BATCH_SIZE)
-            .flat_map(|batch_of_text_chunks| {
-                let texts_for_batch: Vec<String> = batch_of_text_chunks.iter().map(|tc| tc.text.clone()).collect();
-                let embeddings_for_batch = model_arc.encode(&texts_for_batch);
-                bar_chunk_embedding.inc(batch_of_text_chunks.len() as u64);
-                embeddings_for_batch
-            })
-            .collect()
-    });
-    bar_chunk_embedding.finish_with_message("Done embedding chunks.");
+    timed_block("Chunk Embedding Generation", args.verbose, true, || -> Result<()> {
+        // Chunk texts per pending file, kept around for the cache writeback below.
+        let texts_for_files: Vec<Vec<String>> = pending_files
+            .iter()
+            .map(|(_, _, _, range)| chunks[range.clone()].iter().map(|tc| tc.text.clone()).collect())
+            .collect();
 
-    // 5. CALCULATE SIMILARITY AND SORT RESULTS
-    let query_vec: Array1<f32> = Array1::from(query_embedding);
+        // Flatten every file's token-budgeted batches into one global list so a directory of
+        // many small files parallelizes across files, not just within a single file's batches.
+        let batch_jobs: Vec<(Range<usize>, &[String])> = pending_files
+            .iter()
+            .zip(&texts_for_files)
+            .flat_map(|((_, _, _, file_range), texts_for_file)| {
+                let file_start = file_range.start;
+                batching::token_batches(texts_for_file, args.batch_max_tokens)
+                    .into_iter()
+                    .map(move |batch_range| {
+                        let absolute = file_start + batch_range.start..file_start + batch_range.end;
+                        (absolute, &texts_for_file[batch_range])
+                    })
+            })
+            .collect();
 
-    let mut results: Vec<SearchResult> = timed_block("Similarity Calculation & Sorting", args.verbose, true, || {
-        let mut collected_results: Vec<SearchResult> = chunk_embeddings
+        let computed: Vec<(Range<usize>, Vec<Vec<f32>>)> = batch_jobs
             .par_iter()
-            .enumerate()
-            .map(|(i, emb_ref)| {
-                let chunk_vec_view: ArrayView1<f32> = ArrayView1::from(emb_ref); 
-                let sim = cosine_similarity(query_vec.view(), chunk_vec_view);
-                SearchResult {
-                    score: sim,
-                    path: chunks[i].path.clone(),
-                    chunk: chunks[i].text.clone(),
-                }
+            .map(|(absolute_range, batch)| -> Result<(Range<usize>, Vec<Vec<f32>>)> {
+                let embeddings_for_batch = embedder.embed_batch(batch)?;
+                bar_chunk_embedding.inc(batch.len() as u64);
+                Ok((absolute_range.clone(), embeddings_for_batch))
             })
-            .collect();
+            .collect::<Result<Vec<_>>>()?;
 
-        collected_results.par_sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
-        collected_results
-    });
+        for (range, embeddings) in computed {
+            chunk_embeddings[range].clone_from_slice(&embeddings);
+        }
+
+        if let Some(cache) = &mut cache {
+            for ((path, mtime, digest, file_range), texts_for_file) in pending_files.iter().zip(&texts_for_files) {
+                let cache_entries: Vec<(String, Range<usize>, Vec<f32>)> = texts_for_file
+                    .iter()
+                    .cloned()
+                    .zip(chunks[file_range.clone()].iter().map(|tc| tc.range.clone()))
+                    .zip(chunk_embeddings[file_range.clone()].iter().cloned())
+                    .map(|((text, range), embedding)| (text, range, embedding))
+                    .collect();
+                cache.store(path, *mtime, digest, &cache_entries)?;
+            }
+        }
+
+        Ok(())
+    })?;
+    bar_chunk_embedding.finish_with_message("Done embedding chunks.");
 
-    // 6. PRETTY-PRINT THE RESULTS
-    if args.verbose {
-        eprintln!("[VERBOSE] Result Printing Start");
+    if let Some(cache) = &mut cache {
+        let present_paths: Vec<PathBuf> = files.iter().map(|(path, _, _)| path.clone()).collect();
+        cache.evict_missing(&present_paths)?;
     }
 
-    let elapsed_time_total = program_total_start_time.elapsed();
+    let chunk_texts: Vec<String> = chunks.iter().map(|c| c.text.clone()).collect();
+    let bm25_index = bm25::Bm25Index::build(&chunk_texts);
+
+    Ok(Index {
+        chunks,
+        chunk_embeddings,
+        file_count,
+        bm25_index,
+    })
+}
+
+/// Embeds `query_string`, scores every chunk in `index` semantically (cosine) and lexically
+/// (BM25), fuses the two per `args.mode`/`args.alpha`, and returns results sorted best-first.
+fn search(index: &Index, embedder: &dyn Embedder, query_string: &str, args: &Args) -> Result<Vec<SearchResult>> {
+    let query_embedding = embedder
+        .embed_batch(&[query_string.to_string()])?
+        .into_iter()
+        .next()
+        .context("Failed to encode query string")?;
+    let query_vec: Array1<f32> = Array1::from(query_embedding);
+
+    let semantic_scores: Vec<f32> = index
+        .chunk_embeddings
+        .par_iter()
+        .map(|emb_ref| {
+            let chunk_vec_view: ArrayView1<f32> = ArrayView1::from(emb_ref);
+            cosine_similarity(query_vec.view(), chunk_vec_view)
+        })
+        .collect();
+
+    let lexical_scores = index.bm25_index.score(query_string);
+
+    let fused_scores = match args.mode.as_str() {
+        "semantic" => semantic_scores,
+        "lexical" => bm25::normalize_min_max(&lexical_scores),
+        _ => {
+            let semantic_norm = bm25::normalize_min_max(&semantic_scores);
+            let lexical_norm = bm25::normalize_min_max(&lexical_scores);
+            semantic_norm
+                .iter()
+                .zip(lexical_norm.iter())
+                .map(|(s, l)| args.alpha * s + (1.0 - args.alpha) * l)
+                .collect()
+        }
+    };
+
+    let mut results: Vec<SearchResult> = fused_scores
+        .into_iter()
+        .enumerate()
+        .map(|(i, score)| SearchResult {
+            score,
+            path: index.chunks[i].path.clone(),
+            chunk: index.chunks[i].text.clone(),
+            range: index.chunks[i].range.clone(),
+        })
+        .collect();
+
+    results.par_sort_unstable_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(results)
+}
+
+/// Pretty-prints ranked `results` as a table, truncating chunk text for display.
+fn print_results(results: &mut [SearchResult], args: &Args, file_count: usize, query_string: &str, elapsed: Duration) {
     println!(
         "\nFound {} relevant chunks from {} files for query \"{}\" in {:.2} ms. Top {} results:",
         results.len(),
         file_count,
         query_string,
-        elapsed_time_total.as_secs_f64() * 1000.0,
+        elapsed.as_secs_f64() * 1000.0,
         args.limit.min(results.len())
     );
 
-    if !results.is_empty() {
-        let mut table = Table::new();
-        table
-            .load_preset(UTF8_FULL)
-            .set_content_arrangement(ContentArrangement::Dynamic)
-            .set_header(vec![
-                Cell::new("Score"),
-                Cell::new("Matching Text Chunk"),
-                Cell::new("File Path"),
-            ]);
-
-        for result in results.iter_mut().take(args.limit) {
-            const MAX_CHUNK_DISPLAY_LEN: usize = 100;
-            if result.chunk.chars().count() > MAX_CHUNK_DISPLAY_LEN {
-                result.chunk = result.chunk.chars().take(MAX_CHUNK_DISPLAY_LEN).collect::<String>() + "...";
-            }
-            table.add_row(vec![
-                Cell::new(format!("{:.2}", result.score)),
-                Cell::new(&result.chunk),
-                Cell::new(format_path_for_terminal(&result.path)),
-            ]);
-        }
-        println!("{table}");
-    } else {
+    if results.is_empty() {
         println!("No matches found.");
+        return;
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL)
+        .set_content_arrangement(ContentArrangement::Dynamic)
+        .set_header(vec![
+            Cell::new("Score"),
+            Cell::new("Matching Text Chunk"),
+            Cell::new("Byte Range"),
+            Cell::new("File Path"),
+        ]);
+
+    for result in results.iter_mut().take(args.limit) {
+        const MAX_CHUNK_DISPLAY_LEN: usize = 100;
+        if result.chunk.chars().count() > MAX_CHUNK_DISPLAY_LEN {
+            result.chunk = result.chunk.chars().take(MAX_CHUNK_DISPLAY_LEN).collect::<String>() + "...";
+        }
+        table.add_row(vec![
+            Cell::new(format!("{:.2}", result.score)),
+            Cell::new(&result.chunk),
+            Cell::new(format!("{}-{}", result.range.start, result.range.end)),
+            Cell::new(format_path_for_terminal(&result.path)),
+        ]);
+    }
+    println!("{table}");
+}
+
+/// `--watch` mode: keeps re-indexing `args.path` in the background as files change, and
+/// serves queries read from stdin against the live in-memory index, one per line.
+fn run_watch_mode(args: Args, cache: Option<Cache>, embedder: Arc<dyn Embedder>, initial_index: Index) -> Result<()> {
+    let cache = Arc::new(Mutex::new(cache));
+    let index = Arc::new(Mutex::new(initial_index));
+
+    let change_rx = watch::spawn_debounced(args.path.clone(), args.recursive, Duration::from_millis(300))?;
+
+    {
+        let args = args.clone();
+        let cache = Arc::clone(&cache);
+        let index = Arc::clone(&index);
+        let embedder = Arc::clone(&embedder);
+        std::thread::spawn(move || {
+            while change_rx.recv().is_ok() {
+                let reindexed = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    // A panic in an earlier pass poisons the mutex; recover the guard so the
+                    // retry on the next change can still run.
+                    let mut cache_guard = cache.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+                    build_index(&args, cache_guard.as_mut(), embedder.as_ref())
+                }));
+                match reindexed {
+                    Ok(Ok(new_index)) => {
+                        let file_count = new_index.file_count;
+                        let chunk_count = new_index.chunks.len();
+                        *index.lock().unwrap() = new_index;
+                        eprintln!("[watch] reindexed {file_count} files ({chunk_count} chunks)");
+                    }
+                    Ok(Err(e)) => eprintln!("[watch] reindex failed: {e:#}"),
+                    Err(_) => eprintln!("[watch] reindex panicked; keeping the previous index and will retry on the next change"),
+                }
+            }
+        });
     }
-    
-    if args.verbose {
-       eprintln!("[VERBOSE] Result Printing End: {:.2} ms (cumulative)", program_total_start_time.elapsed().as_secs_f64() * 1000.0);
+
+    eprintln!(
+        "[watch] watching '{}' for changes. Enter a query per line (Ctrl-D to exit).",
+        args.path.display()
+    );
+
+    let stdin = std::io::stdin();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        let query_string = line.trim();
+        if query_string.is_empty() {
+            continue;
+        }
+        let start = Instant::now();
+        let index_guard = index.lock().unwrap();
+        let mut results = search(&index_guard, embedder.as_ref(), query_string, &args)?;
+        let file_count = index_guard.file_count;
+        drop(index_guard);
+        print_results(&mut results, &args, file_count, query_string, start.elapsed());
     }
 
     Ok(())
@@ -255,12 +504,12 @@ fn cosine_similarity(a: ArrayView1<f32>, b: ArrayView1<f32>) -> f32 {
 fn format_path_for_terminal(path: &Path) -> String {
     let (path_to_display, is_canonical) = match path.canonicalize() {
         Ok(abs_path) => (abs_path, true),
-        Err(_) => (path.to_path_buf(), false), 
+        Err(_) => (path.to_path_buf(), false),
     };
-    
+
     let path_str = path_to_display.to_string_lossy();
     let encoded_path = utf8_percent_encode(&path_str, PATH_ENCODE_SET).to_string();
-    
+
     if is_canonical && path_str.starts_with("\\\\?\\") {
         format!("file:///{}", path_str.trim_start_matches("\\\\?\\").replace('\\', "/"))
     } else if cfg!(windows) && is_canonical {
@@ -269,4 +518,4 @@ fn format_path_for_terminal(path: &Path) -> String {
     else {
          format!("file://{}", encoded_path)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,51 @@
+use anyhow::{Context, Result};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::time::Duration;
+
+/// Watches `path` for file creations, modifications, and removals, debouncing bursts of
+/// events (e.g. an editor's save-then-touch) into a single signal on the returned channel.
+/// The watcher itself is kept alive for the lifetime of the spawned background thread.
+pub fn spawn_debounced(path: PathBuf, recursive: bool, debounce: Duration) -> Result<Receiver<()>> {
+    let (raw_tx, raw_rx) = mpsc::channel::<()>();
+
+    let mut watcher: RecommendedWatcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            if is_relevant(&event) {
+                let _ = raw_tx.send(());
+            }
+        }
+    })
+    .context("failed to create filesystem watcher")?;
+
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher
+        .watch(&path, mode)
+        .with_context(|| format!("failed to watch '{}' for changes", path.display()))?;
+
+    let (debounced_tx, debounced_rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _watcher = watcher; // keep the watcher alive as long as this thread runs
+        while raw_rx.recv().is_ok() {
+            // Drain and coalesce any further events that arrive within the debounce window.
+            while raw_rx.recv_timeout(debounce).is_ok() {}
+            if debounced_tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(debounced_rx)
+}
+
+fn is_relevant(event: &Event) -> bool {
+    matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    )
+}